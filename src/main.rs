@@ -11,7 +11,11 @@ fn main() -> Result<(), Box<dyn Error>> {
 
     println!("File: {}", file);
 
-    nds::extract(BufReader::new(File::open(file)?))?;
+    nds::extract(
+        BufReader::new(File::open(file)?),
+        true,
+        nds::IconFormat::Both,
+    )?;
 
     Ok(())
 }
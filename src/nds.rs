@@ -2,7 +2,8 @@ use std::{
     error::Error,
     fmt::Debug,
     fs::File,
-    io::{BufRead, BufWriter, Read},
+    io::{BufWriter, Read, Seek, SeekFrom, Take},
+    sync::OnceLock,
 };
 
 use deku::{ctx::Limit, DekuContainerRead, DekuRead};
@@ -11,6 +12,10 @@ use derivative::Derivative;
 use image::{codecs::gif::GifEncoder, Delay, Frame, ImageOutputFormat, RgbaImage};
 use object::{write::Relocation, ObjectSection};
 
+pub mod apng;
+pub mod blz;
+pub mod fs;
+
 pub struct BoundedString {
     data: Vec<u8>,
 }
@@ -149,6 +154,22 @@ pub struct NDSHeader {
     pub debugger_reserved: Vec<u8>,
 }
 
+/// One entry of the ARM9/ARM7 overlay table (`arm9_overlay_offset/size`,
+/// `arm7_overlay_offset/size`): where an overlay loads and which FAT file holds its data.
+#[derive(Derivative, DekuRead)]
+#[derivative(Debug)]
+#[deku(endian = "little")]
+pub struct OverlayEntry {
+    pub overlay_id: u32,
+    pub ram_address: u32,
+    pub ram_size: u32,
+    pub bss_size: u32,
+    pub static_init_start: u32,
+    pub static_init_end: u32,
+    pub file_id: u32,
+    pub compressed_size_and_flags: u32,
+}
+
 // no$gba documentation Nocash @ http://www.problemkaputt.de/gba.htm
 
 #[derive(Derivative, DekuRead)]
@@ -212,55 +233,394 @@ pub struct NDSIcon {
     pub dsi_icon: Option<DSIIcon>,
 }
 
-/// very basic, just converts the contents of arm7 & arm9 to elfs, some more work is needed
-fn dump_elf(header: &NDSHeader, content: &[u8]) -> Result<(), Box<dyn Error>> {
-    let arm9: Vec<u8> = content
-        .iter()
-        .skip(header.arm9_offset as usize)
-        .take(header.arm9_size as usize)
-        .cloned()
-        .collect();
+// CRC-16 (reflected, poly 0xA001, init 0xFFFF) as used throughout the NDS/DSi header and icon banner.
+fn crc16_table() -> &'static [u16; 256] {
+    static TABLE: OnceLock<[u16; 256]> = OnceLock::new();
+    TABLE.get_or_init(|| {
+        let mut table = [0u16; 256];
+        for (n, entry) in table.iter_mut().enumerate() {
+            let mut crc = n as u16;
+            for _ in 0..8 {
+                crc = if crc & 1 != 0 {
+                    (crc >> 1) ^ 0xA001
+                } else {
+                    crc >> 1
+                };
+            }
+            *entry = crc;
+        }
+        table
+    })
+}
 
-    let arm7: Vec<u8> = content
-        .iter()
-        .skip(header.arm7_offset as usize)
-        .take(header.arm7_size as usize)
-        .cloned()
-        .collect();
-
-    let mut arm9_obj = object::write::Object::new(
-        object::BinaryFormat::Elf,
-        object::Architecture::Arm,
-        object::Endianness::Little,
-    );
+fn crc16(data: &[u8]) -> u16 {
+    let table = crc16_table();
+    data.iter().fold(0xFFFFu16, |crc, &byte| {
+        (crc >> 8) ^ table[((crc ^ byte as u16) & 0xFF) as usize]
+    })
+}
 
-    let arm9_section = arm9_obj.add_section(
-        "arm9".to_string().into_bytes(),
-        "arm9".to_string().into_bytes(),
-        object::SectionKind::Text,
-    );
+/// Fixed size of the prefix that makes up `NDSHeader` (through `debugger_reserved`).
+const HEADER_LEN: u64 = 0x180;
+
+/// Seeks `source` to `offset` and returns a reader bounded to `len` bytes, so callers only
+/// pull in the byte range they actually need instead of buffering the whole ROM.
+pub(crate) fn bounded<R: Read + Seek>(
+    source: &mut R,
+    offset: u64,
+    len: u64,
+) -> Result<Take<&mut R>, Box<dyn Error>> {
+    source.seek(SeekFrom::Start(offset))?;
+    Ok(source.take(len))
+}
 
-    arm9_obj.set_section_data(arm9_section, arm9.as_slice(), 4);
+fn read_range<R: Read + Seek>(
+    source: &mut R,
+    offset: u64,
+    len: u64,
+) -> Result<Vec<u8>, Box<dyn Error>> {
+    let mut buf = Vec::new();
+    bounded(source, offset, len)?.read_to_end(&mut buf)?;
+    Ok(buf)
+}
 
-    arm9_obj.write_stream(std::io::BufWriter::new(File::create("out9.elf")?))?;
-    println!("arm9 elf written");
+/// A checksum recorded in the header or icon banner that didn't match its recomputed value.
+#[derive(Debug)]
+pub struct CrcError {
+    pub field: &'static str,
+    pub expected: u16,
+    pub actual: u16,
+}
 
-    let mut arm7_obj = object::write::Object::new(
-        object::BinaryFormat::Elf,
-        object::Architecture::Arm,
-        object::Endianness::Little,
-    );
+impl NDSHeader {
+    /// Header byte range covered by `header_crc`, including the logo and `logo_crc` itself.
+    const HEADER_CRC_LEN: u64 = 0x15E;
+    /// Offset and length of the `logo` field covered by `logo_crc`.
+    const LOGO_OFFSET: u64 = 0xC0;
+    const LOGO_LEN: u64 = 156;
+    /// The secure area is always the 16KiB ROM range 0x4000..0x8000, independent of where
+    /// ARM9 actually loads from.
+    const SECURE_AREA_OFFSET: u64 = 0x4000;
+    const SECURE_AREA_LEN: u64 = 0x4000;
+
+    /// Recomputes `header_crc`, `logo_crc`, and `secure_area_crc` against the ROM bytes read
+    /// from `source` and returns every mismatch found.
+    pub fn verify<R: Read + Seek>(&self, source: &mut R) -> Vec<CrcError> {
+        let mut errors = Vec::new();
+
+        let mut check = |field: &'static str, expected: u16, offset: u64, len: u64| {
+            let Ok(bytes) = read_range(source, offset, len) else {
+                return;
+            };
+            let actual = crc16(&bytes);
+            if actual != expected {
+                errors.push(CrcError {
+                    field,
+                    expected,
+                    actual,
+                });
+            }
+        };
+
+        check("header_crc", self.header_crc, 0, Self::HEADER_CRC_LEN);
+        check("logo_crc", self.logo_crc, Self::LOGO_OFFSET, Self::LOGO_LEN);
+        check(
+            "secure_area_crc",
+            self.secure_area_crc,
+            Self::SECURE_AREA_OFFSET,
+            Self::SECURE_AREA_LEN,
+        );
+
+        errors
+    }
+}
 
-    let arm7_section = arm7_obj.add_section(
-        "arm7".to_string().into_bytes(),
-        "arm7".to_string().into_bytes(),
-        object::SectionKind::Text,
-    );
+impl NDSIcon {
+    /// Banner byte ranges (relative to the icon banner's start) covered by `crc_1..crc_4`.
+    /// `verify` checks `crc_2` from version 2, `crc_3` from version 3 and `crc_4` from 0x103.
+    const CRC_1_RANGE: std::ops::Range<usize> = 0x0020..0x0840;
+    const CRC_2_RANGE: std::ops::Range<usize> = 0x0020..0x0940;
+    const CRC_3_RANGE: std::ops::Range<usize> = 0x0020..0x0A40;
+    const CRC_4_RANGE: std::ops::Range<usize> = 0x1240..0x23C0;
+
+    /// Recomputes `crc_1..crc_4` against the raw icon banner bytes and returns every mismatch
+    /// found, skipping CRCs that aren't defined for this banner's `version`.
+    pub fn verify(&self, banner: &[u8]) -> Vec<CrcError> {
+        let mut errors = Vec::new();
+
+        let mut check = |field: &'static str, expected: u16, range: std::ops::Range<usize>| {
+            let Some(bytes) = banner.get(range) else {
+                return;
+            };
+            let actual = crc16(bytes);
+            if actual != expected {
+                errors.push(CrcError {
+                    field,
+                    expected,
+                    actual,
+                });
+            }
+        };
+
+        check("crc_1", self.crc_1, Self::CRC_1_RANGE);
+        if self.version >= 2 {
+            check("crc_2", self.crc_2, Self::CRC_2_RANGE);
+        }
+        if self.version >= 3 {
+            check("crc_3", self.crc_3, Self::CRC_3_RANGE);
+        }
+        if self.version >= 0x103 {
+            check("crc_4", self.crc_4, Self::CRC_4_RANGE);
+        }
+
+        errors
+    }
+}
+
+/// Hand-rolled ELF32 writer for a loadable, entry-pointed executable image. `object`'s
+/// `write::Object` only targets relocatable (`ET_REL`) object files, where section load
+/// addresses are assigned by a linker rather than authored directly by the producer, so it
+/// can't express what overlay placement needs here: each section/segment at its own fixed
+/// load address, plus an entry point.
+mod elf32 {
+    const EHDR_SIZE: u32 = 52;
+    const PHDR_SIZE: u32 = 32;
+    const SHDR_SIZE: u32 = 40;
+
+    const ET_EXEC: u16 = 2;
+    const EM_ARM: u16 = 40;
+    const PT_LOAD: u32 = 1;
+    const PF_EXEC: u32 = 1;
+    const PF_READ: u32 = 4;
+    const SHT_PROGBITS: u32 = 1;
+    const SHT_STRTAB: u32 = 3;
+    const SHF_ALLOC: u32 = 0x2;
+    const SHF_EXECINSTR: u32 = 0x4;
+
+    fn align4(offset: u32) -> u32 {
+        (offset + 3) & !3
+    }
+
+    /// One loadable region of the image: a name (for the section header), its load address,
+    /// and its raw bytes.
+    pub struct Segment<'a> {
+        pub name: String,
+        pub address: u32,
+        pub data: &'a [u8],
+    }
+
+    /// Builds a full ELF32/ARM/little-endian executable: one `PT_LOAD` program header and one
+    /// `SHT_PROGBITS` section per segment, each placed at its own load address, plus `e_entry`.
+    pub fn build(entry: u32, segments: &[Segment]) -> Vec<u8> {
+        let ph_count = segments.len() as u32;
+
+        let mut offset = EHDR_SIZE + PHDR_SIZE * ph_count;
+        let mut data_offsets = Vec::with_capacity(segments.len());
+        for segment in segments {
+            offset = align4(offset);
+            data_offsets.push(offset);
+            offset += segment.data.len() as u32;
+        }
+
+        let mut shstrtab = vec![0u8]; // index 0 is the empty name
+        let mut name_offsets = Vec::with_capacity(segments.len());
+        for segment in segments {
+            name_offsets.push(shstrtab.len() as u32);
+            shstrtab.extend_from_slice(segment.name.as_bytes());
+            shstrtab.push(0);
+        }
+        let shstrtab_name_offset = shstrtab.len() as u32;
+        shstrtab.extend_from_slice(b".shstrtab\0");
+
+        offset = align4(offset);
+        let shstrtab_offset = offset;
+        offset += shstrtab.len() as u32;
+
+        let sh_offset = align4(offset);
+        let sh_count = ph_count + 2; // null + one per segment + shstrtab
+        let shstrndx = sh_count - 1;
+
+        let mut buf = Vec::new();
+
+        buf.extend_from_slice(&[0x7F, b'E', b'L', b'F', 1, 1, 1, 0, 0, 0, 0, 0, 0, 0, 0, 0]);
+        buf.extend_from_slice(&ET_EXEC.to_le_bytes());
+        buf.extend_from_slice(&EM_ARM.to_le_bytes());
+        buf.extend_from_slice(&1u32.to_le_bytes()); // e_version
+        buf.extend_from_slice(&entry.to_le_bytes());
+        buf.extend_from_slice(&EHDR_SIZE.to_le_bytes()); // e_phoff
+        buf.extend_from_slice(&sh_offset.to_le_bytes()); // e_shoff
+        buf.extend_from_slice(&0u32.to_le_bytes()); // e_flags
+        buf.extend_from_slice(&(EHDR_SIZE as u16).to_le_bytes()); // e_ehsize
+        buf.extend_from_slice(&(PHDR_SIZE as u16).to_le_bytes()); // e_phentsize
+        buf.extend_from_slice(&(ph_count as u16).to_le_bytes());
+        buf.extend_from_slice(&(SHDR_SIZE as u16).to_le_bytes()); // e_shentsize
+        buf.extend_from_slice(&(sh_count as u16).to_le_bytes());
+        buf.extend_from_slice(&(shstrndx as u16).to_le_bytes());
+
+        for (i, segment) in segments.iter().enumerate() {
+            buf.extend_from_slice(&PT_LOAD.to_le_bytes());
+            buf.extend_from_slice(&data_offsets[i].to_le_bytes()); // p_offset
+            buf.extend_from_slice(&segment.address.to_le_bytes()); // p_vaddr
+            buf.extend_from_slice(&segment.address.to_le_bytes()); // p_paddr
+            buf.extend_from_slice(&(segment.data.len() as u32).to_le_bytes()); // p_filesz
+            buf.extend_from_slice(&(segment.data.len() as u32).to_le_bytes()); // p_memsz
+            buf.extend_from_slice(&(PF_READ | PF_EXEC).to_le_bytes());
+            buf.extend_from_slice(&4u32.to_le_bytes()); // p_align
+        }
+
+        for (i, segment) in segments.iter().enumerate() {
+            buf.resize(data_offsets[i] as usize, 0);
+            buf.extend_from_slice(segment.data);
+        }
 
-    arm7_obj.set_section_data(arm7_section, arm7.as_slice(), 4);
+        buf.resize(shstrtab_offset as usize, 0);
+        buf.extend_from_slice(&shstrtab);
 
-    arm7_obj.write_stream(std::io::BufWriter::new(File::create("out7.elf")?))?;
-    println!("arm7 elf written");
+        buf.resize(sh_offset as usize, 0);
+
+        buf.extend_from_slice(&[0u8; SHDR_SIZE as usize]); // SHT_NULL section
+
+        for (i, segment) in segments.iter().enumerate() {
+            buf.extend_from_slice(&name_offsets[i].to_le_bytes()); // sh_name
+            buf.extend_from_slice(&SHT_PROGBITS.to_le_bytes());
+            buf.extend_from_slice(&((SHF_ALLOC | SHF_EXECINSTR) as u32).to_le_bytes());
+            buf.extend_from_slice(&segment.address.to_le_bytes()); // sh_addr
+            buf.extend_from_slice(&data_offsets[i].to_le_bytes()); // sh_offset
+            buf.extend_from_slice(&(segment.data.len() as u32).to_le_bytes()); // sh_size
+            buf.extend_from_slice(&0u32.to_le_bytes()); // sh_link
+            buf.extend_from_slice(&0u32.to_le_bytes()); // sh_info
+            buf.extend_from_slice(&4u32.to_le_bytes()); // sh_addralign
+            buf.extend_from_slice(&0u32.to_le_bytes()); // sh_entsize
+        }
+
+        buf.extend_from_slice(&shstrtab_name_offset.to_le_bytes()); // sh_name
+        buf.extend_from_slice(&SHT_STRTAB.to_le_bytes());
+        buf.extend_from_slice(&0u32.to_le_bytes()); // sh_flags
+        buf.extend_from_slice(&0u32.to_le_bytes()); // sh_addr
+        buf.extend_from_slice(&shstrtab_offset.to_le_bytes());
+        buf.extend_from_slice(&(shstrtab.len() as u32).to_le_bytes());
+        buf.extend_from_slice(&0u32.to_le_bytes());
+        buf.extend_from_slice(&0u32.to_le_bytes());
+        buf.extend_from_slice(&1u32.to_le_bytes()); // sh_addralign
+        buf.extend_from_slice(&0u32.to_le_bytes());
+
+        buf
+    }
+}
+
+fn read_overlays<R: Read + Seek>(
+    source: &mut R,
+    offset: u32,
+    size: u32,
+) -> Result<Vec<OverlayEntry>, Box<dyn Error>> {
+    let bytes = read_range(source, offset as u64, size as u64)?;
+    let mut rest: &[u8] = bytes.as_slice();
+    let mut overlays = Vec::new();
+
+    while rest.len() >= 32 {
+        let ((tail, _), entry) = OverlayEntry::from_bytes((rest, 0))?;
+        overlays.push(entry);
+        rest = tail;
+    }
+
+    Ok(overlays)
+}
+
+/// Builds an ELF image for one of arm9/arm7: a loadable, executable segment at its native
+/// load address plus one segment per overlay at its own load address, and an entry point set
+/// to `entry`.
+fn write_elf<R: Read + Seek>(
+    path: &str,
+    name: &str,
+    entry: u32,
+    load: u32,
+    data: &[u8],
+    source: &mut R,
+    fat: &[fs::FatEntry],
+    overlays: &[OverlayEntry],
+) -> Result<(), Box<dyn Error>> {
+    // Read every overlay's bytes up front into buffers that outlive `elf32::build`'s borrows.
+    let overlay_data = overlays
+        .iter()
+        .map(|overlay| {
+            let file = fat[overlay.file_id as usize];
+            read_range(source, file.start as u64, (file.end - file.start) as u64)
+        })
+        .collect::<Result<Vec<_>, _>>()?;
+
+    let mut segments = vec![elf32::Segment {
+        name: name.to_string(),
+        address: load,
+        data,
+    }];
+    for (overlay, data) in overlays.iter().zip(&overlay_data) {
+        segments.push(elf32::Segment {
+            name: format!("overlay{}", overlay.overlay_id),
+            address: overlay.ram_address,
+            data,
+        });
+    }
+
+    std::fs::write(path, elf32::build(entry, &segments))?;
+    println!("{name} elf written");
+
+    Ok(())
+}
+
+/// Dumps arm7/arm9 (decompressing arm9 if requested) plus their overlays into loadable,
+/// entry-pointed ELF images, using the overlay tables and FAT to place each overlay at its
+/// designated load address.
+fn dump_elf<R: Read + Seek>(
+    header: &NDSHeader,
+    source: &mut R,
+    decompress_arm9: bool,
+) -> Result<(), Box<dyn Error>> {
+    let arm9_raw = read_range(source, header.arm9_offset as u64, header.arm9_size as u64)?;
+
+    let arm9 = if decompress_arm9 {
+        match blz::blz_decode(&arm9_raw) {
+            Some(decoded) => decoded,
+            None => {
+                println!("arm9 does not look BLZ-compressed, dumping raw");
+                arm9_raw
+            }
+        }
+    } else {
+        arm9_raw
+    };
+
+    let arm7 = read_range(source, header.arm7_offset as u64, header.arm7_size as u64)?;
+
+    let fat = fs::parse_fat(source, header.fat_offset, header.fat_size)?;
+    let arm9_overlays =
+        read_overlays(source, header.arm9_overlay_offset, header.arm9_overlay_size)?;
+    let arm7_overlays = read_overlays(
+        source,
+        header.arm7_overlay_offset,
+        header.arm7_overlay_length,
+    )?;
+
+    write_elf(
+        "out9.elf",
+        "arm9",
+        header.arm9_entry,
+        header.arm9_load,
+        &arm9,
+        source,
+        &fat,
+        &arm9_overlays,
+    )?;
+
+    write_elf(
+        "out7.elf",
+        "arm7",
+        header.arm7_entry,
+        header.arm7_load,
+        &arm7,
+        source,
+        &fat,
+        &arm7_overlays,
+    )?;
 
     Ok(())
 }
@@ -323,10 +683,33 @@ fn dump_icon_frame<Blit: FnMut(u32, u32, [u8; 4])>(
     }
 }
 
-fn dump_icon(header: &NDSHeader, content: &[u8]) -> Result<(), Box<dyn Error>> {
-    let icon = NDSIcon::from_bytes((content.split_at(header.icon_banner_offset as usize).1, 0))?.1;
+/// Which format(s) to emit the animated DSi icon in.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum IconFormat {
+    Gif,
+    Apng,
+    Both,
+}
+
+/// Icon banners top out at 0x23C0 bytes (version 0x103, with the animated DSi icon).
+const MAX_BANNER_LEN: u64 = 0x23C0;
+
+fn dump_icon<R: Read + Seek>(
+    header: &NDSHeader,
+    source: &mut R,
+    icon_format: IconFormat,
+) -> Result<(), Box<dyn Error>> {
+    let banner = read_range(source, header.icon_banner_offset as u64, MAX_BANNER_LEN)?;
+    let icon = NDSIcon::from_bytes((banner.as_slice(), 0))?.1;
     println!("{:#?}", icon);
 
+    for err in icon.verify(&banner) {
+        eprintln!(
+            "warning: icon {} mismatch (expected {:#06x}, got {:#06x})",
+            err.field, err.expected, err.actual
+        );
+    }
+
     let mut icon_png = image::RgbaImage::new(32, 32);
 
     dump_icon_frame(
@@ -342,13 +725,6 @@ fn dump_icon(header: &NDSHeader, content: &[u8]) -> Result<(), Box<dyn Error>> {
     )?;
 
     if let Some(dsi) = icon.dsi_icon {
-        let mut gif = GifEncoder::new(BufWriter::new(File::create(format!(
-            "dsi_icon_{}.gif",
-            header.gamecode.str()
-        ))?));
-
-        gif.set_repeat(image::codecs::gif::Repeat::Infinite)?;
-
         let mut frames = vec![];
         for seq in dsi.sequence {
             if seq == 0 {
@@ -376,32 +752,84 @@ fn dump_icon(header: &NDSHeader, content: &[u8]) -> Result<(), Box<dyn Error>> {
                 anim,
             );
 
-            frames.push(Frame::from_parts(
-                buffer,
-                0,
-                0,
-                Delay::from_saturating_duration(std::time::Duration::from_millis(
-                    (((dur as f64) / 60.) * 1000.) as u64,
-                )),
-            ));
+            frames.push((buffer, dur));
         }
 
-        gif.encode_frames(frames)?;
+        if icon_format == IconFormat::Gif || icon_format == IconFormat::Both {
+            let mut gif = GifEncoder::new(BufWriter::new(File::create(format!(
+                "dsi_icon_{}.gif",
+                header.gamecode.str()
+            ))?));
+
+            gif.set_repeat(image::codecs::gif::Repeat::Infinite)?;
+
+            let gif_frames = frames.iter().map(|(buffer, dur)| {
+                Frame::from_parts(
+                    buffer.clone(),
+                    0,
+                    0,
+                    Delay::from_saturating_duration(std::time::Duration::from_millis(
+                        ((*dur as f64 / 60.) * 1000.) as u64,
+                    )),
+                )
+            });
+
+            gif.encode_frames(gif_frames)?;
+        }
+
+        if icon_format == IconFormat::Apng || icon_format == IconFormat::Both {
+            let apng_frames = frames
+                .iter()
+                .map(|(buffer, dur)| apng::ApngFrame {
+                    image: buffer.clone(),
+                    delay_numerator: *dur as u16,
+                })
+                .collect::<Vec<_>>();
+
+            apng::write_apng(
+                BufWriter::new(File::create(format!(
+                    "dsi_icon_{}.png",
+                    header.gamecode.str()
+                ))?),
+                &apng_frames,
+            )?;
+        }
     }
 
     Ok(())
 }
 
-pub fn extract<T: BufRead>(mut nds_file: T) -> Result<NDSHeader, Box<dyn Error>> {
-    let mut content: Vec<u8> = Vec::new();
+pub fn extract<T: Read + Seek>(
+    mut nds_file: T,
+    decompress_arm9: bool,
+    icon_format: IconFormat,
+) -> Result<NDSHeader, Box<dyn Error>> {
+    let header_buf = read_range(&mut nds_file, 0, HEADER_LEN)?;
+    let header = NDSHeader::from_bytes((header_buf.as_slice(), 0))?.1;
+    println!("{:#?}", &header);
 
-    // TODO: make this streamed
-    nds_file.read_to_end(&mut content)?;
+    for err in header.verify(&mut nds_file) {
+        eprintln!(
+            "warning: header {} mismatch (expected {:#06x}, got {:#06x})",
+            err.field, err.expected, err.actual
+        );
+    }
 
-    let header = NDSHeader::from_bytes((content.as_slice(), 0))?.1;
-    println!("{:#?}", &header);
-    dump_elf(&header, content.as_slice())?;
-    dump_icon(&header, content.as_slice())?;
+    dump_elf(&header, &mut nds_file, decompress_arm9)?;
+    dump_icon(&header, &mut nds_file, icon_format)?;
+    fs::extract_files(&header, &mut nds_file, "extracted")?;
 
     Ok(header)
 }
+
+#[cfg(test)]
+mod tests {
+    use super::crc16;
+
+    #[test]
+    fn crc16_matches_known_check_value() {
+        // This crc16 is poly 0xA001 reflected with init 0xFFFF and no final XOR (CRC-16/MODBUS),
+        // whose published check value over ASCII "123456789" is 0x4B37.
+        assert_eq!(crc16(b"123456789"), 0x4B37);
+    }
+}
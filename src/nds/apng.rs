@@ -0,0 +1,35 @@
+//! Writes an animated PNG (APNG) from a sequence of RGBA frames, preserving full color and
+//! native frame timing instead of GIF's 256-color palette and 1/100s delay quantization.
+
+use std::{error::Error, io::Write};
+
+use image::RgbaImage;
+
+/// One APNG frame: the decoded image and its delay expressed as `numerator/60` seconds, to
+/// match the DSi icon animation's native 1/60s timing.
+pub struct ApngFrame {
+    pub image: RgbaImage,
+    pub delay_numerator: u16,
+}
+
+/// Writes `frames` out as a looping APNG.
+pub fn write_apng<W: Write>(writer: W, frames: &[ApngFrame]) -> Result<(), Box<dyn Error>> {
+    let (width, height) = frames
+        .first()
+        .map(|f| f.image.dimensions())
+        .unwrap_or((32, 32));
+
+    let mut encoder = png::Encoder::new(writer, width, height);
+    encoder.set_color(png::ColorType::Rgba);
+    encoder.set_depth(png::BitDepth::Eight);
+    encoder.set_animated(frames.len() as u32, 0)?;
+
+    let mut writer = encoder.write_header()?;
+    for frame in frames {
+        writer.set_frame_delay(frame.delay_numerator, 60)?;
+        writer.write_image_data(frame.image.as_raw())?;
+    }
+    writer.finish()?;
+
+    Ok(())
+}
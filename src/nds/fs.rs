@@ -0,0 +1,144 @@
+//! Parses the FNT (File Name Table) and FAT (File Allocation Table) and writes the packed
+//! file tree out to a real directory on disk, reading each file's bytes from the ROM on
+//! demand instead of holding the whole tree in memory at once.
+
+use std::{
+    error::Error,
+    fs,
+    io::{self, Read, Seek, SeekFrom},
+    path::{Path, PathBuf},
+};
+
+use super::NDSHeader;
+
+/// A single FAT entry: the `[start, end)` byte range of one file within the ROM.
+#[derive(Debug, Clone, Copy)]
+pub(crate) struct FatEntry {
+    pub start: u32,
+    pub end: u32,
+}
+
+fn read_u8<R: Read>(source: &mut R) -> io::Result<u8> {
+    let mut buf = [0u8; 1];
+    source.read_exact(&mut buf)?;
+    Ok(buf[0])
+}
+
+fn read_u16<R: Read>(source: &mut R) -> io::Result<u16> {
+    let mut buf = [0u8; 2];
+    source.read_exact(&mut buf)?;
+    Ok(u16::from_le_bytes(buf))
+}
+
+fn read_u32<R: Read>(source: &mut R) -> io::Result<u32> {
+    let mut buf = [0u8; 4];
+    source.read_exact(&mut buf)?;
+    Ok(u32::from_le_bytes(buf))
+}
+
+pub(crate) fn parse_fat<R: Read + Seek>(
+    source: &mut R,
+    fat_offset: u32,
+    fat_size: u32,
+) -> Result<Vec<FatEntry>, Box<dyn Error>> {
+    source.seek(SeekFrom::Start(fat_offset as u64))?;
+    (0..fat_size / 8)
+        .map(|_| {
+            Ok(FatEntry {
+                start: read_u32(source)?,
+                end: read_u32(source)?,
+            })
+        })
+        .collect()
+}
+
+/// Main-table entry for one directory: offset of its subtable and the id of its first file.
+struct FntDirEntry {
+    subtable_offset: u32,
+    first_file_id: u16,
+}
+
+fn read_fnt_dir_entry<R: Read + Seek>(
+    source: &mut R,
+    fnt_offset: u32,
+    dir_id: u16,
+) -> Result<FntDirEntry, Box<dyn Error>> {
+    source.seek(SeekFrom::Start(
+        fnt_offset as u64 + (dir_id & 0xFFF) as u64 * 8,
+    ))?;
+    Ok(FntDirEntry {
+        subtable_offset: read_u32(source)?,
+        first_file_id: read_u16(source)?,
+    })
+}
+
+/// Rejects FNT entry names that could escape `dest` when joined onto it: path separators or
+/// `..` components embedded in a corrupted or crafted ROM.
+fn sanitize_name(name: &str) -> Result<&str, Box<dyn Error>> {
+    if name.is_empty() || name.contains('/') || name.contains('\\') || name == ".." {
+        return Err(format!("unsafe FNT entry name: {name:?}").into());
+    }
+    Ok(name)
+}
+
+/// Recursively walks a directory's subtable, writing files under `dest` and recursing into
+/// subdirectories, using `fat` to slice each file's bytes out of the ROM on demand.
+fn walk_dir<R: Read + Seek>(
+    source: &mut R,
+    fnt_offset: u32,
+    fat: &[FatEntry],
+    dir_id: u16,
+    dest: &Path,
+) -> Result<(), Box<dyn Error>> {
+    fs::create_dir_all(dest)?;
+
+    let dir = read_fnt_dir_entry(source, fnt_offset, dir_id)?;
+    source.seek(SeekFrom::Start(
+        fnt_offset as u64 + dir.subtable_offset as u64,
+    ))?;
+    let mut file_id = dir.first_file_id;
+
+    loop {
+        let tag = read_u8(source)?;
+        if tag == 0x00 {
+            break;
+        }
+
+        let name_len = (tag & 0x7F) as usize;
+        let mut name_buf = vec![0u8; name_len];
+        source.read_exact(&mut name_buf)?;
+        let name = String::from_utf8_lossy(&name_buf).to_string();
+        let name = sanitize_name(&name)?;
+
+        if tag & 0x80 != 0 {
+            let sub_dir_id = read_u16(source)?;
+            let resume = source.stream_position()?;
+            walk_dir(source, fnt_offset, fat, sub_dir_id, &dest.join(name))?;
+            source.seek(SeekFrom::Start(resume))?;
+        } else {
+            let entry = fat[file_id as usize];
+            let resume = source.stream_position()?;
+            let mut reader =
+                super::bounded(source, entry.start as u64, (entry.end - entry.start) as u64)?;
+            io::copy(&mut reader, &mut fs::File::create(dest.join(name))?)?;
+            source.seek(SeekFrom::Start(resume))?;
+            file_id += 1;
+        }
+    }
+
+    Ok(())
+}
+
+/// Extracts the ROM's packed file tree (via its FNT/FAT) into real files and directories
+/// under `dest`.
+pub fn extract_files<R: Read + Seek>(
+    header: &NDSHeader,
+    source: &mut R,
+    dest: impl AsRef<Path>,
+) -> Result<(), Box<dyn Error>> {
+    let dest: PathBuf = dest.as_ref().to_path_buf();
+    let fat = parse_fat(source, header.fat_offset, header.fat_size)?;
+
+    // The root directory's id is 0xF000; `read_fnt_dir_entry` masks to the low 12 bits.
+    walk_dir(source, header.fnt_offset, &fat, 0xF000, &dest)
+}